@@ -6,7 +6,8 @@ use rerun::{self, Rgba32, EXTERNAL_DATA_LOADER_INCOMPATIBLE_EXIT_CODE};
 /// Any executable on your `$PATH` with a name that starts with [`rerun-loader-`] will be
 /// treated as an external data-loader.
 ///
-/// This particular one will log collada files as [`Mesh3d`](https://docs.rs/rerun/latest/rerun/struct.Mesh3D.html),
+/// This particular one will log OBJ, STL, glTF, and COLLADA files as
+/// [`Mesh3d`](https://docs.rs/rerun/latest/rerun/struct.Mesh3D.html),
 /// and return a special exit code to indicate that it doesn't support anything else.
 #[derive(argh::FromArgs, Debug)]
 struct Args {
@@ -35,19 +36,43 @@ struct Args {
 
     /// deprecated: alias for `--static`
     #[argh(switch)]
-    _timeless: bool,
+    timeless: bool,
 
     /// optionally mark data to be logged statically
     #[argh(arg_name = "static", switch)]
-    _statically: bool,
+    statically: bool,
 
     /// optional timestamps to log at (e.g. `--time sim_time=1709203426`) (repeatable)
     #[argh(option)]
-    _time: Vec<String>,
+    time: Vec<String>,
 
     /// optional sequences to log at (e.g. `--sequence sim_frame=42`) (repeatable)
     #[argh(option)]
-    _sequence: Vec<String>,
+    sequence: Vec<String>,
+}
+
+impl Args {
+    /// Whether data should be logged statically, pinning it across all timelines.
+    fn is_static(&self) -> bool {
+        self.statically || self.timeless
+    }
+
+    /// The application ID to use, following the external DataLoader contract's fallback chain:
+    /// opened-application-id, then application-id, then a generic default.
+    fn resolved_application_id(&self) -> &str {
+        self.opened_application_id
+            .as_deref()
+            .or(self.application_id.as_deref())
+            .unwrap_or("external_data_loader")
+    }
+
+    /// The recording ID to use, if any, following the fallback chain: opened-recording-id, then
+    /// recording-id. `None` lets the recording stream fall back to its own default.
+    fn resolved_recording_id(&self) -> Option<&str> {
+        self.opened_recording_id
+            .as_deref()
+            .or(self.recording_id.as_deref())
+    }
 }
 
 fn extension(path: &std::path::Path) -> String {
@@ -58,30 +83,156 @@ fn extension(path: &std::path::Path) -> String {
         .to_string()
 }
 
+/// Converts a `[r, g, b, a]` color in the normalized `0.0..=1.0` range, as used throughout
+/// `mesh_loader`'s material fields, into the `Rgba32` rerun expects for its color factors.
+fn rgba32(color: &[f32; 4]) -> Rgba32 {
+    Rgba32::from_unmultiplied_rgba(
+        (color[0] * 255.0) as u8,
+        (color[1] * 255.0) as u8,
+        (color[2] * 255.0) as u8,
+        (color[3] * 255.0) as u8,
+    )
+}
+
+/// Loads the diffuse texture referenced by `mat`, if any, resolving its path relative to the
+/// directory the source mesh was loaded from (as texture paths in mesh files are themselves
+/// relative to the file that references them).
+fn load_albedo_texture(
+    source_path: &std::path::Path,
+    mat: &mesh_loader::Material,
+) -> Option<rerun::TensorData> {
+    let diffuse_texture = mat.textures.diffuse.as_ref()?;
+    let texture_path = source_path
+        .parent()
+        .map(|dir| dir.join(diffuse_texture))
+        .unwrap_or_else(|| diffuse_texture.clone());
+
+    match image::open(&texture_path) {
+        Ok(image) => match rerun::TensorData::from_image(image) {
+            Ok(tensor) => Some(tensor),
+            Err(err) => {
+                eprintln!("Warning: failed to interpret albedo texture {texture_path:?}: {err}");
+                None
+            }
+        },
+        Err(err) => {
+            eprintln!("Warning: failed to load albedo texture {texture_path:?}: {err}");
+            None
+        }
+    }
+}
+
+/// Splits a repeatable `timeline=value` argument (as passed via `--time`/`--sequence`) into
+/// its timeline name and raw value, warning and returning `None` if it isn't well-formed.
+fn parse_timeline_arg<'a>(arg: &'a str, flag: &str) -> Option<(&'a str, &'a str)> {
+    match arg.split_once('=') {
+        Some((timeline, value)) => Some((timeline, value)),
+        None => {
+            eprintln!("Warning: ignoring malformed `{flag}` argument {arg:?}, expected `timeline=value`");
+            None
+        }
+    }
+}
+
+/// Applies the `--time`/`--sequence` arguments to `rec`'s timepoint, so that every subsequent
+/// `log` call lands on the timelines the viewer asked us to use instead of "static" fallback.
+fn set_time_from_args(rec: &rerun::RecordingStream, args: &Args) {
+    for sequence in &args.sequence {
+        let Some((timeline, value)) = parse_timeline_arg(sequence, "--sequence") else {
+            continue;
+        };
+        match value.parse::<i64>() {
+            Ok(seq) => rec.set_time_sequence(timeline, seq),
+            Err(err) => eprintln!(
+                "Warning: ignoring malformed `--sequence` argument {sequence:?}: {err}"
+            ),
+        }
+    }
+
+    for time in &args.time {
+        let Some((timeline, value)) = parse_timeline_arg(time, "--time") else {
+            continue;
+        };
+        match value.parse::<i64>() {
+            Ok(nanos) => rec.set_time_nanos(timeline, nanos),
+            Err(err) => eprintln!("Warning: ignoring malformed `--time` argument {time:?}: {err}"),
+        }
+    }
+}
+
+/// Loads `filepath` with the `mesh_loader` method appropriate for its extension, falling back
+/// to format auto-detection for any other extension. [`main`] only lets through the extensions
+/// special-cased here, so that fallback is not reached from it.
+fn load_scene(loader: &mesh_loader::Loader, filepath: &std::path::Path) -> anyhow::Result<mesh_loader::Scene> {
+    Ok(match extension(filepath).as_str() {
+        "obj" => loader.load_obj(filepath)?,
+        "stl" => loader.load_stl(filepath)?,
+        "gltf" | "glb" => loader.load_gltf(filepath)?,
+        "dae" => loader.load_collada(filepath)?,
+        _ => loader.load(filepath)?,
+    })
+}
+
+/// The entity path a given mesh should be logged at: a child of `root` named after the mesh
+/// itself when the source format gave it a name, or its index in the scene otherwise. This
+/// keeps meshes from a multi-part scene from overwriting one another at `root`.
+fn mesh_entity_path(root: &rerun::EntityPath, index: usize, mesh: &mesh_loader::Mesh) -> rerun::EntityPath {
+    if mesh.name.is_empty() {
+        root.join(&rerun::EntityPath::from(format!("mesh_{index}")))
+    } else {
+        root.join(&rerun::EntityPath::from(mesh.name.as_str()))
+    }
+}
+
 fn load_mesh(rec: &rerun::RecordingStream, args: &Args) -> anyhow::Result<()> {
     let loader = mesh_loader::Loader::default();
-    let scene = loader.load_collada(&args.filepath)?;
+    let scene = load_scene(&loader, &args.filepath)?;
+
+    set_time_from_args(rec, args);
+
+    let root = if let Some(entity_path_prefix) = &args.entity_path_prefix {
+        rerun::EntityPath::from(entity_path_prefix.deref())
+    } else {
+        rerun::EntityPath::from_file_path(&args.filepath)
+    };
+
+    let default_material = mesh_loader::Material::default();
+
+    for (index, mesh) in scene.meshes.iter().enumerate() {
+        let mat = mesh
+            .material_index
+            .and_then(|material_index| usize::try_from(material_index).ok())
+            .and_then(|material_index| scene.materials.get(material_index))
+            .unwrap_or(&default_material);
 
-    for (mesh, mat) in scene.meshes.iter().zip(scene.materials.iter()) {
         let mut mesh3d = rerun::Mesh3D::new(&mesh.vertices);
 
         if !mesh.normals.is_empty() && !mesh.normals[0].is_empty() {
             mesh3d = mesh3d.with_vertex_normals(&mesh.normals);
         }
 
+        if !mesh.faces.is_empty() {
+            mesh3d = mesh3d.with_triangle_indices(&mesh.faces);
+        }
+
+        if !mesh.texcoords[0].is_empty() {
+            mesh3d = mesh3d.with_vertex_texcoords(&mesh.texcoords[0]);
+        }
+
         if let Some(diffuse) = &mat.color.diffuse {
-            mesh3d = mesh3d.with_albedo_factor(Rgba32::from_unmultiplied_rgba(
-                diffuse[0] as u8,
-                diffuse[1] as u8,
-                diffuse[2] as u8,
-                diffuse[3] as u8,
-            ));
+            mesh3d = mesh3d.with_albedo_factor(rgba32(diffuse));
         }
 
-        if let Some(entity_path_prefix) = &args.entity_path_prefix {
-            rec.log(entity_path_prefix.deref(), &mesh3d)?;
+        if let Some(albedo_texture) = load_albedo_texture(&args.filepath, mat) {
+            mesh3d = mesh3d.with_albedo_texture(albedo_texture);
+        }
+
+        let entity_path = mesh_entity_path(&root, index, mesh);
+
+        if args.is_static() {
+            rec.log_static(entity_path, &mesh3d)?;
         } else {
-            rec.log(rerun::EntityPath::from_file_path(&args.filepath), &mesh3d)?;
+            rec.log(entity_path, &mesh3d)?;
         }
     }
 
@@ -91,30 +242,21 @@ fn load_mesh(rec: &rerun::RecordingStream, args: &Args) -> anyhow::Result<()> {
 fn main() -> anyhow::Result<()> {
     let args: Args = argh::from_env();
 
+    const SUPPORTED_EXTENSIONS: &[&str] = &["obj", "stl", "gltf", "glb", "dae"];
+
     let is_file = args.filepath.is_file();
-    let is_collada_file = extension(&args.filepath) == "dae";
+    let is_supported_file = SUPPORTED_EXTENSIONS.contains(&extension(&args.filepath).as_str());
 
     // Inform the Rerun Viewer that we do not support that kind of file.
-    if !is_file || !is_collada_file {
+    if !is_file || !is_supported_file {
         #[allow(clippy::exit)]
         std::process::exit(EXTERNAL_DATA_LOADER_INCOMPATIBLE_EXIT_CODE);
     }
 
     let rec: rerun::RecordingStream = {
-        let mut rec = rerun::RecordingStreamBuilder::new(
-            args.opened_application_id.as_deref().unwrap_or(
-                args.application_id
-                    .as_deref()
-                    .unwrap_or("external_data_loader"),
-            ),
-        );
-
-        let recording_id = args
-            .opened_recording_id
-            .as_ref()
-            .or(args.recording_id.as_ref());
+        let mut rec = rerun::RecordingStreamBuilder::new(args.resolved_application_id());
 
-        if let Some(recording_id) = recording_id {
+        if let Some(recording_id) = args.resolved_recording_id() {
             rec = rec.recording_id(recording_id);
         };
 